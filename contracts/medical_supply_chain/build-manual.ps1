@@ -7,6 +7,9 @@ Write-Host "🏗️  Manual Contract Build (Alternative Method)" -ForegroundColo
 $CONTRACT_DIR = $PSScriptRoot
 Set-Location $CONTRACT_DIR
 
+# Keep build output here rather than in the workspace target directory
+$env:CARGO_TARGET_DIR = "target"
+
 # Clean previous builds
 Write-Host "🧹 Cleaning previous builds..." -ForegroundColor Yellow
 cargo clean
@@ -9,6 +9,9 @@ echo "🏗️  Manual Contract Build (Alternative Method)"
 CONTRACT_DIR="$(dirname "$0")"
 cd "$CONTRACT_DIR"
 
+# Keep build output here rather than in the workspace target directory
+export CARGO_TARGET_DIR=target
+
 # Clean previous builds
 echo "🧹 Cleaning previous builds..."
 cargo clean
@@ -10,6 +10,9 @@ echo "🏗️  Building Medical Supply Chain Contract..."
 # Navigate to contract directory
 cd "$(dirname "$0")/.."
 
+# Keep build output here rather than in the workspace target directory
+export CARGO_TARGET_DIR=target
+
 # Clean previous builds
 echo "🧹 Cleaning previous builds..."
 cargo clean
@@ -0,0 +1,792 @@
+//! Command-line tool for manufacturers and logistics operators.
+//!
+//! Registers batches (one at a time or from CSV), moves custody and reads products back
+//! from a deployed `medical_supply_chain` contract, printing a table or JSON.
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::fmt;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use ink::primitives::AccountId;
+use medical_supply_chain_client::{Client, ClientError, Product, Runtime, TransferRecord};
+use serde::{Deserialize, Serialize};
+use subxt::utils::AccountId32;
+
+#[derive(Parser)]
+#[command(name = "supply-chain", about = "Register batches and move custody on the medical supply chain contract")]
+struct Cli {
+    /// Node RPC endpoint
+    #[arg(long, default_value = "ws://127.0.0.1:9944")]
+    url: String,
+    /// Contract address, SS58 or 0x-prefixed hex
+    #[arg(long, env = "SUPPLY_CHAIN_CONTRACT")]
+    contract: String,
+    /// Secret URI of the signing account
+    #[arg(long, env = "SUPPLY_CHAIN_SURI", default_value = "//Alice")]
+    suri: String,
+    /// Output format
+    #[arg(long, value_enum, default_value_t = Format::Table, global = true)]
+    format: Format,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Register one batch, or every row of a CSV file
+    Register(RegisterArgs),
+    /// Transfer custody of a product
+    Transfer {
+        product_id: u32,
+        to: String,
+        location: String,
+        /// Units to move; the caller's whole holding when omitted
+        #[arg(long)]
+        quantity: Option<u32>,
+    },
+    /// Show a product
+    Verify { product_id: u32 },
+    /// Show a product's custody transfers
+    History { product_id: u32 },
+    /// Authorize or revoke a manufacturer (owner only)
+    Authorize {
+        account: String,
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// Export products with their custody history
+    Export {
+        /// Only products registered by this manufacturer
+        #[arg(long)]
+        manufacturer: Option<String>,
+        /// Write to a file instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Args, Debug)]
+struct RegisterArgs {
+    /// CSV file with name,batch_number,manufacturer_name,quantity,mfg_date,expiry_date,category columns
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    #[arg(long, required_unless_present = "csv", conflicts_with = "csv")]
+    name: Option<String>,
+    #[arg(long, required_unless_present = "csv")]
+    batch_number: Option<String>,
+    #[arg(long, required_unless_present = "csv")]
+    manufacturer_name: Option<String>,
+    #[arg(long, required_unless_present = "csv")]
+    quantity: Option<u32>,
+    /// Manufacturing date in milliseconds since the Unix epoch
+    #[arg(long, required_unless_present = "csv")]
+    mfg_date: Option<u64>,
+    /// Expiry date in milliseconds since the Unix epoch
+    #[arg(long, required_unless_present = "csv")]
+    expiry_date: Option<u64>,
+    #[arg(long, required_unless_present = "csv")]
+    category: Option<String>,
+}
+
+/// One batch to register, as read from CSV
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+struct Batch {
+    name: String,
+    batch_number: String,
+    manufacturer_name: String,
+    quantity: u32,
+    mfg_date: u64,
+    expiry_date: u64,
+    category: String,
+}
+
+impl Batch {
+    /// Bytes the contract charges a storage deposit for
+    fn data_len(&self) -> u32 {
+        (self.name.len() + self.batch_number.len() + self.manufacturer_name.len() + self.category.len()) as u32
+    }
+}
+
+/// Errors reported to the user
+#[derive(Debug)]
+enum CliError {
+    /// Bad argument or input file
+    Input(String),
+    /// Call failed on the node or in the contract
+    Client(ClientError),
+    /// Registration failed part way through a CSV file, after registering the earlier rows
+    Row { row: usize, error: ClientError, registered: Vec<Registration> },
+    Io(io::Error),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Input(reason) => write!(f, "{reason}"),
+            CliError::Client(error) => write!(f, "{error}"),
+            CliError::Row { row, error, .. } => write!(f, "row {row}: {error}"),
+            CliError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<ClientError> for CliError {
+    fn from(error: ClientError) -> Self {
+        CliError::Client(error)
+    }
+}
+
+impl From<io::Error> for CliError {
+    fn from(error: io::Error) -> Self {
+        CliError::Io(error)
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct ProductView {
+    id: u32,
+    name: String,
+    batch_number: String,
+    manufacturer: String,
+    manufacturer_name: String,
+    quantity: u32,
+    mfg_date: u64,
+    expiry_date: u64,
+    category: String,
+    current_holder: String,
+    is_authentic: bool,
+    created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transfers: Option<Vec<TransferView>>,
+}
+
+impl From<Product> for ProductView {
+    fn from(product: Product) -> Self {
+        ProductView {
+            id: product.id,
+            name: product.name,
+            batch_number: product.batch_number,
+            manufacturer: ss58(product.manufacturer),
+            manufacturer_name: product.manufacturer_name,
+            quantity: product.quantity,
+            mfg_date: product.mfg_date,
+            expiry_date: product.expiry_date,
+            category: product.category,
+            current_holder: ss58(product.current_holder),
+            is_authentic: product.is_authentic,
+            created_at: product.created_at,
+            transfers: None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct TransferView {
+    from: String,
+    to: String,
+    location: String,
+    quantity: u32,
+    timestamp: u64,
+    verified: bool,
+}
+
+impl From<TransferRecord> for TransferView {
+    fn from(transfer: TransferRecord) -> Self {
+        TransferView {
+            from: ss58(transfer.from),
+            to: ss58(transfer.to),
+            location: transfer.location,
+            quantity: transfer.quantity,
+            timestamp: transfer.timestamp,
+            verified: transfer.verified,
+        }
+    }
+}
+
+/// CSV row, product ID and batch number of a registered batch
+type Registration = (usize, u32, String);
+
+/// Command output, printable as an aligned table or as JSON
+struct Report {
+    headers: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+    json: serde_json::Value,
+}
+
+impl Report {
+    fn registrations(registered: &[Registration]) -> Self {
+        Report {
+            headers: &["ROW", "PRODUCT ID", "BATCH"],
+            rows: registered
+                .iter()
+                .map(|(row, product_id, batch_number)| vec![row.to_string(), product_id.to_string(), batch_number.clone()])
+                .collect(),
+            json: registered
+                .iter()
+                .map(|(row, product_id, batch_number)| {
+                    serde_json::json!({ "row": row, "product_id": product_id, "batch_number": batch_number })
+                })
+                .collect(),
+        }
+    }
+
+    fn products(products: &[ProductView], json: serde_json::Value) -> Self {
+        Report {
+            headers: &["ID", "NAME", "BATCH", "QUANTITY", "HOLDER", "AUTHENTIC", "EXPIRY"],
+            rows: products
+                .iter()
+                .map(|product| {
+                    vec![
+                        product.id.to_string(),
+                        product.name.clone(),
+                        product.batch_number.clone(),
+                        product.quantity.to_string(),
+                        product.current_holder.clone(),
+                        product.is_authentic.to_string(),
+                        product.expiry_date.to_string(),
+                    ]
+                })
+                .collect(),
+            json,
+        }
+    }
+
+    fn render(&self, format: Format, out: &mut dyn Write) -> io::Result<()> {
+        if format == Format::Json {
+            return writeln!(out, "{}", serde_json::to_string_pretty(&self.json)?);
+        }
+
+        let widths: Vec<usize> = self
+            .headers
+            .iter()
+            .enumerate()
+            .map(|(i, header)| self.rows.iter().map(|row| row[i].len()).fold(header.len(), usize::max))
+            .collect();
+        let headers: Vec<String> = self.headers.iter().map(|header| header.to_string()).collect();
+        for row in std::iter::once(&headers).chain(&self.rows) {
+            let line: Vec<String> = row.iter().zip(&widths).map(|(cell, width)| format!("{cell:<width$}")).collect();
+            writeln!(out, "{}", line.join("  ").trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+fn ss58(account: AccountId) -> String {
+    AccountId32(*AsRef::<[u8; 32]>::as_ref(&account)).to_string()
+}
+
+fn parse_account(value: &str) -> Result<AccountId, CliError> {
+    let bytes = match value.strip_prefix("0x") {
+        Some(hex) => hex::decode(hex).ok().and_then(|bytes| <[u8; 32]>::try_from(bytes).ok()),
+        None => AccountId32::from_str(value).ok().map(|account| account.0),
+    };
+    bytes.map(AccountId::from).ok_or_else(|| CliError::Input(format!("invalid account {value}")))
+}
+
+fn read_batches(path: &Path) -> Result<Vec<Batch>, CliError> {
+    let file = File::open(path).map_err(|error| CliError::Input(format!("{}: {error}", path.display())))?;
+    csv::Reader::from_reader(file)
+        .deserialize()
+        .enumerate()
+        .map(|(i, row)| row.map_err(|error| CliError::Input(format!("{} row {}: {error}", path.display(), i + 1))))
+        .collect()
+}
+
+fn batches(args: RegisterArgs) -> Result<Vec<Batch>, CliError> {
+    if let Some(path) = args.csv {
+        return read_batches(&path);
+    }
+    // clap requires every field when no CSV file is given
+    Ok(vec![Batch {
+        name: args.name.unwrap_or_default(),
+        batch_number: args.batch_number.unwrap_or_default(),
+        manufacturer_name: args.manufacturer_name.unwrap_or_default(),
+        quantity: args.quantity.unwrap_or_default(),
+        mfg_date: args.mfg_date.unwrap_or_default(),
+        expiry_date: args.expiry_date.unwrap_or_default(),
+        category: args.category.unwrap_or_default(),
+    }])
+}
+
+/// Register batches in order, stopping at the first one the contract rejects
+fn register<R: Runtime>(client: &mut Client<R>, batches: Vec<Batch>) -> Result<Report, CliError> {
+    let mut registered = Vec::new();
+    for (i, batch) in batches.into_iter().enumerate() {
+        let row = i + 1;
+        let data_len = batch.data_len();
+        let cost = client
+            .query(|contract| contract.quote_registration(data_len))
+            .map_err(|error| CliError::Row { row, error, registered: std::mem::take(&mut registered) })?;
+        let receipt = client
+            .send(|contract| {
+                contract
+                    .register_product(
+                        batch.name.clone(),
+                        batch.batch_number.clone(),
+                        batch.manufacturer_name.clone(),
+                        batch.quantity,
+                        batch.mfg_date,
+                        batch.expiry_date,
+                        batch.category.clone(),
+                    )
+                    .transferred_value(cost)
+            })
+            .map_err(|error| CliError::Row { row, error, registered: std::mem::take(&mut registered) })?;
+        registered.push((row, receipt.value, batch.batch_number));
+    }
+
+    Ok(Report::registrations(&registered))
+}
+
+fn fetch_product<R: Runtime>(client: &mut Client<R>, product_id: u32) -> Result<ProductView, CliError> {
+    client
+        .query(|contract| contract.verify_product(product_id))?
+        .map(ProductView::from)
+        .ok_or_else(|| CliError::Input(format!("product {product_id} not found")))
+}
+
+fn fetch_history<R: Runtime>(client: &mut Client<R>, product_id: u32) -> Result<Vec<TransferView>, CliError> {
+    let history = client.query(|contract| contract.get_transfer_history(product_id))?;
+    Ok(history.into_iter().map(TransferView::from).collect())
+}
+
+/// Run a command against the contract, writing its report to `out`
+fn run<R: Runtime>(client: &mut Client<R>, command: Command, format: Format, out: &mut dyn Write) -> Result<(), CliError> {
+    let report = match command {
+        Command::Register(args) => {
+            let from_csv = args.csv.is_some();
+            match register(client, batches(args)?) {
+                Ok(report) => report,
+                // A single batch from the arguments has no row to point at
+                Err(CliError::Row { error, .. }) if !from_csv => return Err(CliError::Client(error)),
+                // Batches already on chain are listed so a rerun can skip them
+                Err(CliError::Row { row, error, registered }) => {
+                    if !registered.is_empty() {
+                        Report::registrations(&registered).render(format, out)?;
+                    }
+                    return Err(CliError::Row { row, error, registered });
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        Command::Transfer {
+            product_id,
+            to,
+            location,
+            quantity,
+        } => {
+            let recipient = parse_account(&to)?;
+            match quantity {
+                Some(quantity) => client.send(|contract| contract.transfer_units(product_id, recipient, quantity, location.clone()))?,
+                None => client.send(|contract| contract.transfer_custody(product_id, recipient, location.clone()))?,
+            };
+            Report {
+                headers: &["PRODUCT ID", "TO", "LOCATION"],
+                rows: vec![vec![product_id.to_string(), ss58(recipient), location.clone()]],
+                json: serde_json::json!({ "product_id": product_id, "to": ss58(recipient), "location": location, "quantity": quantity }),
+            }
+        }
+        Command::Verify { product_id } => {
+            let product = fetch_product(client, product_id)?;
+            let json = serde_json::to_value(&product).map_err(io::Error::from)?;
+            Report::products(&[product], json)
+        }
+        Command::History { product_id } => {
+            let history = fetch_history(client, product_id)?;
+            Report {
+                headers: &["FROM", "TO", "LOCATION", "QUANTITY", "TIMESTAMP"],
+                rows: history
+                    .iter()
+                    .map(|transfer| {
+                        vec![
+                            transfer.from.clone(),
+                            transfer.to.clone(),
+                            transfer.location.clone(),
+                            transfer.quantity.to_string(),
+                            transfer.timestamp.to_string(),
+                        ]
+                    })
+                    .collect(),
+                json: serde_json::to_value(&history).map_err(io::Error::from)?,
+            }
+        }
+        Command::Authorize { account, revoke } => {
+            let manufacturer = parse_account(&account)?;
+            client.send(|contract| contract.authorize_manufacturer(manufacturer, !revoke))?;
+            Report {
+                headers: &["ACCOUNT", "AUTHORIZED"],
+                rows: vec![vec![ss58(manufacturer), (!revoke).to_string()]],
+                json: serde_json::json!({ "account": ss58(manufacturer), "authorized": !revoke }),
+            }
+        }
+        Command::Export { manufacturer, output } => {
+            let product_ids = match manufacturer {
+                Some(manufacturer) => {
+                    let manufacturer = parse_account(&manufacturer)?;
+                    client.query(|contract| contract.get_products_by_manufacturer(manufacturer))?
+                }
+                None => (1..client.query(|contract| contract.get_next_product_id())?).collect(),
+            };
+            let mut products = Vec::new();
+            for product_id in product_ids {
+                let mut product = fetch_product(client, product_id)?;
+                product.transfers = Some(fetch_history(client, product_id)?);
+                products.push(product);
+            }
+            let report = Report::products(&products, serde_json::to_value(&products).map_err(io::Error::from)?);
+            if let Some(path) = output {
+                let mut file = File::create(&path)?;
+                return Ok(report.render(format, &mut file)?);
+            }
+            report
+        }
+    };
+    Ok(report.render(format, out)?)
+}
+
+mod node {
+    use std::str::FromStr;
+
+    use ink::primitives::AccountId;
+    use medical_supply_chain_client::{ContractCall, Runtime, Weight};
+    use scale::{Decode, Encode};
+    use subxt::dynamic::Value;
+    use subxt::{OnlineClient, PolkadotConfig};
+    use subxt_signer::sr25519::Keypair;
+    use subxt_signer::SecretUri;
+
+    #[derive(Encode, Decode)]
+    struct WeightArg {
+        #[codec(compact)]
+        ref_time: u64,
+        #[codec(compact)]
+        proof_size: u64,
+    }
+
+    impl From<Weight> for WeightArg {
+        fn from(weight: Weight) -> Self {
+            WeightArg {
+                ref_time: weight.ref_time,
+                proof_size: weight.proof_size,
+            }
+        }
+    }
+
+    #[derive(Decode)]
+    enum StorageDeposit {
+        Refund(#[allow(dead_code)] u128),
+        Charge(#[allow(dead_code)] u128),
+    }
+
+    #[derive(Decode)]
+    struct ExecReturnValue {
+        #[allow(dead_code)]
+        flags: u32,
+        data: Vec<u8>,
+    }
+
+    /// Leading fields of the `ContractsApi_call` result; later fields vary between node versions
+    #[derive(Decode)]
+    struct ContractExecResult {
+        #[allow(dead_code)]
+        gas_consumed: WeightArg,
+        #[allow(dead_code)]
+        gas_required: WeightArg,
+        #[allow(dead_code)]
+        storage_deposit: StorageDeposit,
+        debug_message: Vec<u8>,
+        result: Result<ExecReturnValue, DispatchError>,
+    }
+
+    /// Dispatch error kept as the index of its variant
+    struct DispatchError(u8);
+
+    impl Decode for DispatchError {
+        fn decode<I: scale::Input>(input: &mut I) -> Result<Self, scale::Error> {
+            Ok(DispatchError(input.read_byte()?))
+        }
+    }
+
+    /// Contracts node reached over RPC, signing with one sr25519 key
+    pub struct NodeRuntime {
+        api: OnlineClient<PolkadotConfig>,
+        signer: Keypair,
+        executor: tokio::runtime::Runtime,
+    }
+
+    impl NodeRuntime {
+        pub fn connect(url: &str, suri: &str) -> Result<Self, String> {
+            let executor = tokio::runtime::Runtime::new().map_err(|error| error.to_string())?;
+            let api = executor.block_on(OnlineClient::<PolkadotConfig>::from_url(url)).map_err(|error| error.to_string())?;
+            let uri = SecretUri::from_str(suri).map_err(|error| error.to_string())?;
+            let signer = Keypair::from_uri(&uri).map_err(|error| error.to_string())?;
+            Ok(NodeRuntime { api, signer, executor })
+        }
+
+        /// Account the runtime signs as
+        pub fn account(&self) -> AccountId {
+            AccountId::from(self.signer.public_key().to_account_id().0)
+        }
+    }
+
+    impl Runtime for NodeRuntime {
+        fn dry_run(&mut self, origin: AccountId, call: &ContractCall) -> Result<Vec<u8>, String> {
+            let args = (
+                origin,
+                call.dest,
+                call.value,
+                Some(WeightArg::from(call.gas_limit)),
+                call.storage_deposit_limit,
+                call.data.clone(),
+            )
+                .encode();
+            let result: ContractExecResult = self
+                .executor
+                .block_on(async {
+                    self.api.runtime_api().at_latest().await?.call_raw("ContractsApi_call", Some(&args)).await
+                })
+                .map_err(|error| error.to_string())?;
+
+            match result.result {
+                Ok(value) => Ok(value.data),
+                Err(DispatchError(index)) => Err(format!(
+                    "call failed with dispatch error {index}: {}",
+                    String::from_utf8_lossy(&result.debug_message)
+                )),
+            }
+        }
+
+        fn submit(&mut self, _origin: AccountId, call: &ContractCall) -> Result<Vec<Vec<u8>>, String> {
+            let dest: &[u8; 32] = call.dest.as_ref();
+            let tx = subxt::dynamic::tx(
+                "Contracts",
+                "call",
+                vec![
+                    ("dest", Value::unnamed_variant("Id", [Value::from_bytes(dest)])),
+                    ("value", Value::u128(call.value)),
+                    (
+                        "gas_limit",
+                        Value::named_composite([
+                            ("ref_time", Value::u128(call.gas_limit.ref_time.into())),
+                            ("proof_size", Value::u128(call.gas_limit.proof_size.into())),
+                        ]),
+                    ),
+                    (
+                        "storage_deposit_limit",
+                        match call.storage_deposit_limit {
+                            Some(limit) => Value::unnamed_variant("Some", [Value::u128(limit)]),
+                            None => Value::unnamed_variant("None", []),
+                        },
+                    ),
+                    ("data", Value::from_bytes(&call.data)),
+                ],
+            );
+
+            let events = self
+                .executor
+                .block_on(async {
+                    self.api
+                        .tx()
+                        .sign_and_submit_then_watch_default(&tx, &self.signer)
+                        .await?
+                        .wait_for_in_block()
+                        .await?
+                        .wait_for_success()
+                        .await
+                })
+                .map_err(|error| error.to_string())?;
+
+            // Keep only this contract's events; the data is the contract's own event encoding
+            let mut emitted = Vec::new();
+            for event in events.iter() {
+                let event = event.map_err(|error| error.to_string())?;
+                if event.pallet_name() != "Contracts" || event.variant_name() != "ContractEmitted" {
+                    continue;
+                }
+                let (contract, data) = <([u8; 32], Vec<u8>)>::decode(&mut event.field_bytes()).map_err(|error| error.to_string())?;
+                if &contract == dest {
+                    emitted.push(data);
+                }
+            }
+            Ok(emitted)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = parse_account(&cli.contract).and_then(|contract| {
+        let runtime = node::NodeRuntime::connect(&cli.url, &cli.suri).map_err(|error| CliError::Client(ClientError::Runtime(error)))?;
+        let signer = runtime.account();
+        let mut client = Client::new(runtime, contract, signer);
+        run(&mut client, cli.command, cli.format, &mut io::stdout().lock())
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use medical_supply_chain_client::mock::MockRuntime;
+    use medical_supply_chain_client::ContractError;
+
+    // Well-known development key, not an ink! test account
+    const ALICE_SS58: &str = "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY";
+
+    fn get_test_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+        ink::env::test::default_accounts::<ink::env::DefaultEnvironment>()
+    }
+
+    fn setup_client() -> Client<MockRuntime> {
+        Client::new(MockRuntime::default(), AccountId::from([0x42; 32]), get_test_accounts().alice)
+    }
+
+    fn respond<T: scale::Encode>(client: &mut Client<MockRuntime>, message: &str, value: T) {
+        let selector = client.metadata().selector(message).unwrap();
+        client.runtime_mut().respond(selector, value);
+    }
+
+    fn run_command(client: &mut Client<MockRuntime>, args: &[&str]) -> Result<String, CliError> {
+        let cli = Cli::try_parse_from(["supply-chain", "--contract", ALICE_SS58].iter().chain(args)).unwrap();
+        let mut out = Vec::new();
+        run(client, cli.command, cli.format, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    const CSV_HEADER: &str = "name,batch_number,manufacturer_name,quantity,mfg_date,expiry_date,category\n";
+
+    fn write_csv(rows: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(file, "{CSV_HEADER}{rows}").unwrap();
+        file
+    }
+
+    #[ink::test]
+    fn accounts_parse_from_ss58_and_hex() {
+        let alice = AccountId::from(<[u8; 32]>::try_from(hex::decode("d43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d").unwrap()).unwrap());
+
+        assert_eq!(parse_account(ALICE_SS58).unwrap(), alice);
+        assert_eq!(
+            parse_account("0xd43593c715fdd31c61141abd04a99fd6822c8558854ccde39a5684e7a56da27d").unwrap(),
+            alice
+        );
+        assert_eq!(ss58(alice), ALICE_SS58);
+        assert!(parse_account("0x1234").is_err());
+    }
+
+    #[ink::test]
+    fn csv_batches_register_in_order() {
+        let mut client = setup_client();
+        respond(&mut client, "quote_registration", 0u128);
+        respond(&mut client, "register_product", Ok::<u32, ContractError>(7));
+
+        let csv = write_csv(
+            "Amoxicillin 500mg,AMX-001,Pharma Corp,1000,1704067200000,1767225600000,Antibiotic\n\
+             Paracetamol 500mg,PAR-001,Pharma Corp,500,1704067200000,1767225600000,Analgesic\n",
+        );
+
+        let output = run_command(&mut client, &["register", "--csv", csv.path().to_str().unwrap(), "--format", "json"]).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(json[1], serde_json::json!({ "row": 2, "product_id": 7, "batch_number": "PAR-001" }));
+        assert_eq!(client.runtime().submitted.len(), 2);
+    }
+
+    #[ink::test]
+    fn failed_csv_row_still_reports_earlier_products() {
+        let mut client = setup_client();
+        respond(&mut client, "quote_registration", 0u128);
+        respond(&mut client, "register_product", Ok::<u32, ContractError>(7));
+        let selector = client.metadata().selector("register_product").unwrap();
+        client.runtime_mut().then_respond(selector, Err::<u32, ContractError>(ContractError::NotAuthorizedManufacturer));
+
+        let csv = write_csv(
+            "Amoxicillin 500mg,AMX-001,Pharma Corp,1000,1704067200000,1767225600000,Antibiotic\n\
+             Paracetamol 500mg,PAR-001,Pharma Corp,500,1704067200000,1767225600000,Analgesic\n",
+        );
+        let cli = Cli::try_parse_from(["supply-chain", "--contract", ALICE_SS58, "register", "--csv", csv.path().to_str().unwrap()]).unwrap();
+        let mut out = Vec::new();
+        let error = run(&mut client, cli.command, cli.format, &mut out).unwrap_err();
+
+        assert_eq!(error.to_string(), "row 2: contract error: NotAuthorizedManufacturer");
+        let output = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines, ["ROW  PRODUCT ID  BATCH", "1    7           AMX-001"]);
+        assert_eq!(client.runtime().submitted.len(), 1);
+    }
+
+    #[ink::test]
+    fn verify_prints_table() {
+        let accounts = get_test_accounts();
+
+        // Take a real product from the contract so the mock answers with genuine encoding
+        let mut contract = medical_supply_chain::MedicalSupplyChain::new();
+        let product_id = contract.register_product(
+            "Test Medicine".to_string(),
+            "BATCH-001".to_string(),
+            "Test Pharma Ltd".to_string(),
+            1000,
+            1704067200000, // Jan 1, 2024
+            1767225600000, // Jan 1, 2026
+            "Antibiotic".to_string(),
+        ).unwrap();
+        contract.transfer_custody(product_id, accounts.bob, "Warehouse".to_string()).unwrap();
+
+        let mut client = setup_client();
+        respond(&mut client, "verify_product", contract.verify_product(product_id));
+
+        let output = run_command(&mut client, &["verify", "1"]).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("ID  NAME           BATCH      QUANTITY  HOLDER"));
+        assert_eq!(
+            lines[1],
+            format!("1   Test Medicine  BATCH-001  1000      {}  true       1767225600000", ss58(accounts.bob))
+        );
+    }
+
+    #[ink::test]
+    fn contract_errors_are_reported() {
+        let mut client = setup_client();
+        respond(&mut client, "authorize_manufacturer", Err::<(), ContractError>(ContractError::OnlyOwner));
+        respond(&mut client, "quote_registration", 0u128);
+        respond(&mut client, "register_product", Err::<u32, ContractError>(ContractError::NotAuthorizedManufacturer));
+
+        let error = run_command(&mut client, &["authorize", ALICE_SS58]).unwrap_err();
+        assert_eq!(error.to_string(), "contract error: OnlyOwner");
+
+        let error = run_command(
+            &mut client,
+            &[
+                "register", "--name", "Test", "--batch-number", "B1", "--manufacturer-name", "Pharma",
+                "--quantity", "10", "--mfg-date", "1", "--expiry-date", "2", "--category", "Antibiotic",
+            ],
+        )
+        .unwrap_err();
+        assert_eq!(error.to_string(), "contract error: NotAuthorizedManufacturer");
+        assert!(client.runtime().submitted.is_empty());
+    }
+
+    #[ink::test]
+    fn register_needs_fields_or_csv() {
+        assert!(Cli::try_parse_from(["supply-chain", "--contract", ALICE_SS58, "register", "--name", "Test"]).is_err());
+        assert!(Cli::try_parse_from(["supply-chain", "--contract", ALICE_SS58, "register", "--csv", "batches.csv"]).is_ok());
+    }
+}
@@ -0,0 +1,501 @@
+//! Typed client for the `medical_supply_chain` contract.
+//!
+//! Messages are built through the call builder ink! generates for the contract, so every
+//! message is available with its real argument and return types. Calls are turned into
+//! `Contracts::call` extrinsics and handed to a [`Runtime`], which signs, submits and
+//! dry-runs them; [`mock::MockRuntime`] stands in for a node in tests.
+
+use std::fmt;
+
+use ink::codegen::TraitCallBuilder;
+use ink::env::call::{
+    utils::{ReturnType, Set},
+    Call, CallBuilder, ExecutionInput, FromAccountId,
+};
+use ink::env::DefaultEnvironment;
+use ink::metadata::InkProject;
+use ink::primitives::AccountId;
+use ink::LangError;
+use medical_supply_chain::MedicalSupplyChainRef;
+use scale::{Compact, Decode, DecodeAll, Encode};
+
+pub use medical_supply_chain::{Error as ContractError, Product, TransferRecord};
+
+pub type Balance = <DefaultEnvironment as ink::env::Environment>::Balance;
+
+/// Call index of `call` in the contracts pallet
+pub const CONTRACTS_CALL_INDEX: u8 = 6;
+
+/// A message call returned by the contract's generated call builder
+pub type Message<Args, Output> =
+    CallBuilder<DefaultEnvironment, Set<Call<DefaultEnvironment>>, Set<ExecutionInput<Args>>, Set<ReturnType<Output>>>;
+
+/// Call builder with one typed method per contract message
+pub type Messages = <MedicalSupplyChainRef as TraitCallBuilder>::Builder;
+
+/// Errors surfaced to client code
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClientError {
+    /// The runtime could not execute or submit the call
+    Runtime(String),
+    /// The contract could not dispatch the message
+    Lang(LangError),
+    /// The message returned an error
+    Contract(ContractError),
+    /// Output or event data did not match the expected type
+    Decode(String),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientError::Runtime(reason) => write!(f, "runtime error: {reason}"),
+            ClientError::Lang(error) => write!(f, "message could not be dispatched: {error:?}"),
+            ClientError::Contract(error) => write!(f, "contract error: {error:?}"),
+            ClientError::Decode(reason) => write!(f, "decode error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<ContractError> for ClientError {
+    fn from(error: ContractError) -> Self {
+        ClientError::Contract(error)
+    }
+}
+
+impl From<scale::Error> for ClientError {
+    fn from(error: scale::Error) -> Self {
+        ClientError::Decode(error.to_string())
+    }
+}
+
+pub type Result<T> = core::result::Result<T, ClientError>;
+
+/// Return type of the contract's fallible messages
+pub type ContractResult<T> = core::result::Result<T, ContractError>;
+
+/// Weight limit for a contract call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weight {
+    pub ref_time: u64,
+    pub proof_size: u64,
+}
+
+impl Default for Weight {
+    fn default() -> Self {
+        Weight {
+            ref_time: 50_000_000_000,
+            proof_size: 1_000_000,
+        }
+    }
+}
+
+/// A call to the contract, ready to be wrapped in an extrinsic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractCall {
+    pub dest: AccountId,
+    pub value: Balance,
+    pub gas_limit: Weight,
+    pub storage_deposit_limit: Option<Balance>,
+    pub data: Vec<u8>, // selector followed by the SCALE-encoded arguments
+}
+
+impl ContractCall {
+    /// Message selector the call data starts with
+    pub fn selector(&self) -> [u8; 4] {
+        let mut selector = [0; 4];
+        selector.copy_from_slice(&self.data[..4]);
+        selector
+    }
+
+    /// Encode as `Contracts::call` under the runtime's contracts pallet index, ready to sign
+    pub fn encode_call(&self, pallet_index: u8) -> Vec<u8> {
+        let mut out = vec![pallet_index, CONTRACTS_CALL_INDEX];
+        out.push(0); // MultiAddress::Id
+        self.dest.encode_to(&mut out);
+        Compact(self.value).encode_to(&mut out);
+        Compact(self.gas_limit.ref_time).encode_to(&mut out);
+        Compact(self.gas_limit.proof_size).encode_to(&mut out);
+        self.storage_deposit_limit.map(Compact).encode_to(&mut out);
+        self.data.encode_to(&mut out);
+        out
+    }
+}
+
+/// Node connection used by the client
+pub trait Runtime {
+    /// Execute a call without committing it, returning the contract's raw output
+    fn dry_run(&mut self, origin: AccountId, call: &ContractCall) -> core::result::Result<Vec<u8>, String>;
+
+    /// Sign and submit a call, returning the data of each event the contract emitted
+    fn submit(&mut self, origin: AccountId, call: &ContractCall) -> core::result::Result<Vec<Vec<u8>>, String>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Decode)]
+pub struct ProductRegistered {
+    pub product_id: u32,
+    pub manufacturer: AccountId,
+    pub name: String,
+    pub batch_number: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Decode)]
+pub struct CustodyTransferred {
+    pub product_id: u32,
+    pub from: AccountId,
+    pub to: AccountId,
+    pub location: String,
+    pub quantity: u32,
+}
+
+/// Contract event decoded from its emitted data
+///
+/// Only `ProductRegistered` and `CustodyTransferred` are decoded into typed fields; every other
+/// event is returned as `Other` with its metadata label and the raw SCALE-encoded data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    ProductRegistered(ProductRegistered),
+    CustodyTransferred(CustodyTransferred),
+    /// Any other event, by its metadata label
+    Other { label: String, data: Vec<u8> },
+}
+
+/// Selectors and event indices taken from the contract's metadata
+pub struct Metadata {
+    project: InkProject,
+}
+
+impl Metadata {
+    /// Metadata generated from the linked contract crate
+    ///
+    /// The contract crate must be linked with `std` and without `ink-as-dependency`, or the
+    /// metadata symbol is missing and linking fails.
+    pub fn generate() -> Self {
+        extern "Rust" {
+            // Emitted by #[ink::contract] in std builds of the contract crate
+            #[link_name = "__ink_generate_metadata"]
+            fn generate_metadata() -> InkProject;
+        }
+        Metadata {
+            project: unsafe { generate_metadata() },
+        }
+    }
+
+    /// Selector of a message by label
+    pub fn selector(&self, label: &str) -> Option<[u8; 4]> {
+        self.project
+            .spec()
+            .messages()
+            .iter()
+            .find(|message| message.label() == label)
+            .map(|message| message.selector().to_bytes().try_into().unwrap())
+    }
+
+    /// Label of the message a selector dispatches to
+    pub fn message_label(&self, selector: [u8; 4]) -> Option<&str> {
+        self.project
+            .spec()
+            .messages()
+            .iter()
+            .find(|message| message.selector().to_bytes() == selector)
+            .map(|message| message.label().as_str())
+    }
+
+    /// Decode emitted event data, which starts with the event's index in the metadata
+    pub fn decode_event(&self, data: &[u8]) -> Result<Event> {
+        let (index, fields) = data.split_first().ok_or_else(|| ClientError::Decode("empty event data".into()))?;
+        let label = self
+            .project
+            .spec()
+            .events()
+            .get(usize::from(*index))
+            .map(|event| event.label().as_str())
+            .ok_or_else(|| ClientError::Decode(format!("unknown event index {index}")))?;
+
+        Ok(match label {
+            "ProductRegistered" => Event::ProductRegistered(ProductRegistered::decode_all(&mut &fields[..])?),
+            "CustodyTransferred" => Event::CustodyTransferred(CustodyTransferred::decode_all(&mut &fields[..])?),
+            _ => Event::Other {
+                label: label.to_string(),
+                data: fields.to_vec(),
+            },
+        })
+    }
+}
+
+/// Result of a submitted message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt<T> {
+    pub value: T,
+    pub events: Vec<Event>,
+}
+
+/// Client for one deployed contract, signing as one account
+pub struct Client<R> {
+    runtime: R,
+    signer: AccountId,
+    contract: MedicalSupplyChainRef,
+    metadata: Metadata,
+    gas_limit: Weight,
+    storage_deposit_limit: Option<Balance>,
+}
+
+impl<R: Runtime> Client<R> {
+    pub fn new(runtime: R, contract: AccountId, signer: AccountId) -> Self {
+        Client {
+            runtime,
+            signer,
+            contract: <MedicalSupplyChainRef as FromAccountId<DefaultEnvironment>>::from_account_id(contract),
+            metadata: Metadata::generate(),
+            gas_limit: Weight::default(),
+            storage_deposit_limit: None,
+        }
+    }
+
+    /// Set the weight limit for calls that do not set their own gas limit
+    pub fn with_gas_limit(mut self, gas_limit: Weight) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+
+    /// Cap the storage deposit a call may take
+    pub fn with_storage_deposit_limit(mut self, limit: Balance) -> Self {
+        self.storage_deposit_limit = Some(limit);
+        self
+    }
+
+    pub fn runtime(&self) -> &R {
+        &self.runtime
+    }
+
+    pub fn runtime_mut(&mut self) -> &mut R {
+        &mut self.runtime
+    }
+
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Build the contract call for a message
+    pub fn build_call<Args: Encode, Output>(&mut self, message: impl FnOnce(&mut Messages) -> Message<Args, Output>) -> ContractCall {
+        let params = message(self.contract.call_mut()).params();
+        let gas_limit = match params.gas_limit() {
+            0 => self.gas_limit,
+            ref_time => Weight { ref_time, ..self.gas_limit },
+        };
+        ContractCall {
+            dest: *params.callee(),
+            value: *params.transferred_value(),
+            gas_limit,
+            storage_deposit_limit: self.storage_deposit_limit,
+            data: params.exec_input().encode(),
+        }
+    }
+
+    /// Dry-run a message and decode its return value
+    pub fn query<Args: Encode, Output: Decode>(&mut self, message: impl FnOnce(&mut Messages) -> Message<Args, Output>) -> Result<Output> {
+        let call = self.build_call(message);
+        self.dry_run(&call)
+    }
+
+    /// Submit a fallible message once a dry run shows it succeeds
+    pub fn send<Args: Encode, T: Decode>(
+        &mut self,
+        message: impl FnOnce(&mut Messages) -> Message<Args, ContractResult<T>>,
+    ) -> Result<Receipt<T>> {
+        let call = self.build_call(message);
+        let value = self.dry_run::<ContractResult<T>>(&call)??;
+
+        let events = self
+            .runtime
+            .submit(self.signer, &call)
+            .map_err(ClientError::Runtime)?
+            .iter()
+            .map(|data| self.metadata.decode_event(data))
+            .collect::<Result<_>>()?;
+
+        Ok(Receipt { value, events })
+    }
+
+    fn dry_run<Output: Decode>(&mut self, call: &ContractCall) -> Result<Output> {
+        let output = self.runtime.dry_run(self.signer, call).map_err(ClientError::Runtime)?;
+        ink::MessageResult::<Output>::decode_all(&mut &output[..])?.map_err(ClientError::Lang)
+    }
+}
+
+pub mod mock {
+    use std::collections::{HashMap, VecDeque};
+
+    use super::*;
+
+    /// Runtime that answers calls from scripted outputs by selector and records submissions
+    #[derive(Default)]
+    pub struct MockRuntime {
+        /// Outputs per message, used in turn with the last one repeating
+        outputs: HashMap<[u8; 4], VecDeque<Vec<u8>>>,
+        events: HashMap<[u8; 4], Vec<Vec<u8>>>,
+        /// Calls submitted so far, with their origin
+        pub submitted: Vec<(AccountId, ContractCall)>,
+    }
+
+    impl MockRuntime {
+        /// Answer a message with a successfully dispatched return value
+        pub fn respond<T: Encode>(&mut self, selector: [u8; 4], value: T) -> &mut Self {
+            self.respond_raw(selector, ink::MessageResult::Ok(value).encode())
+        }
+
+        /// Answer a message with raw output bytes
+        pub fn respond_raw(&mut self, selector: [u8; 4], output: Vec<u8>) -> &mut Self {
+            self.outputs.insert(selector, VecDeque::from([output]));
+            self
+        }
+
+        /// Answer later calls of a message with this value once its earlier outputs have been used
+        pub fn then_respond<T: Encode>(&mut self, selector: [u8; 4], value: T) -> &mut Self {
+            self.outputs.entry(selector).or_default().push_back(ink::MessageResult::Ok(value).encode());
+            self
+        }
+
+        /// Emit this event data whenever the message is submitted
+        pub fn emit(&mut self, selector: [u8; 4], data: Vec<u8>) -> &mut Self {
+            self.events.entry(selector).or_default().push(data);
+            self
+        }
+    }
+
+    impl Runtime for MockRuntime {
+        fn dry_run(&mut self, _origin: AccountId, call: &ContractCall) -> core::result::Result<Vec<u8>, String> {
+            let outputs = self
+                .outputs
+                .get_mut(&call.selector())
+                .filter(|outputs| !outputs.is_empty())
+                .ok_or_else(|| format!("no output scripted for selector {:02x?}", call.selector()))?;
+            match outputs.len() {
+                1 => Ok(outputs[0].clone()),
+                _ => Ok(outputs.pop_front().unwrap_or_default()),
+            }
+        }
+
+        fn submit(&mut self, origin: AccountId, call: &ContractCall) -> core::result::Result<Vec<Vec<u8>>, String> {
+            self.submitted.push((origin, call.clone()));
+            Ok(self.events.get(&call.selector()).cloned().unwrap_or_default())
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::mock::MockRuntime;
+    use super::*;
+    use medical_supply_chain::MedicalSupplyChain;
+
+    fn get_test_accounts() -> ink::env::test::DefaultAccounts<DefaultEnvironment> {
+        ink::env::test::default_accounts::<DefaultEnvironment>()
+    }
+
+    fn setup_client() -> Client<MockRuntime> {
+        let accounts = get_test_accounts();
+        Client::new(MockRuntime::default(), AccountId::from([0x42; 32]), accounts.alice)
+    }
+
+    fn register_call(messages: &mut Messages) -> Message<impl Encode, ContractResult<u32>> {
+        messages.register_product(
+            "Test Medicine".to_string(),
+            "BATCH-001".to_string(),
+            "Test Pharma Ltd".to_string(),
+            1000,
+            1704067200000, // Jan 1, 2024
+            1767225600000, // Jan 1, 2026
+            "Antibiotic".to_string(),
+        )
+    }
+
+    #[ink::test]
+    fn builds_contracts_call_extrinsic() {
+        let mut client = setup_client().with_gas_limit(Weight { ref_time: 1_000, proof_size: 64 });
+        let call = client.build_call(register_call);
+        
+        assert_eq!(Some(call.selector()), client.metadata().selector("register_product"));
+        assert_eq!(client.metadata().message_label(call.selector()), Some("register_product"));
+        
+        let encoded = call.encode_call(8);
+        assert_eq!(&encoded[..3], &[8, CONTRACTS_CALL_INDEX, 0]);
+        assert_eq!(&encoded[3..35], &[0x42; 32]);
+        // Compact value 0, ref_time 1_000, proof_size 64, no deposit limit
+        assert_eq!(&encoded[35..40], &[0x00, 0xa1, 0x0f, 0x01, 0x01]);
+        assert_eq!(encoded[40], 0);
+        assert_eq!(&encoded[41..], call.data.encode().as_slice());
+    }
+
+    #[ink::test]
+    fn send_decodes_value_and_contract_events() {
+        let accounts = get_test_accounts();
+        
+        // Record the events the contract really emits for these calls
+        let mut contract = MedicalSupplyChain::new();
+        let product_id = contract.register_product(
+            "Test Medicine".to_string(),
+            "BATCH-001".to_string(),
+            "Test Pharma Ltd".to_string(),
+            1000,
+            1704067200000,
+            1767225600000,
+            "Antibiotic".to_string(),
+        ).unwrap();
+        contract.transfer_custody(product_id, accounts.bob, "Warehouse".to_string()).unwrap();
+        let emitted: Vec<Vec<u8>> = ink::env::test::recorded_events().map(|event| event.data).collect();
+        
+        let mut client = setup_client();
+        let selector = client.metadata().selector("register_product").unwrap();
+        let runtime = client.runtime_mut();
+        runtime.respond(selector, Ok::<u32, ContractError>(product_id));
+        for data in emitted {
+            runtime.emit(selector, data);
+        }
+        
+        let receipt = client.send(register_call).unwrap();
+        assert_eq!(receipt.value, product_id);
+        assert!(receipt.events.contains(&Event::ProductRegistered(ProductRegistered {
+            product_id,
+            manufacturer: accounts.alice,
+            name: "Test Medicine".to_string(),
+            batch_number: "BATCH-001".to_string(),
+        })));
+        assert!(receipt.events.contains(&Event::CustodyTransferred(CustodyTransferred {
+            product_id,
+            from: accounts.alice,
+            to: accounts.bob,
+            location: "Warehouse".to_string(),
+            quantity: 1000,
+        })));
+        assert!(receipt.events.iter().any(|event| matches!(event, Event::Other { label, .. } if label == "Transfer")));
+        assert_eq!(client.runtime().submitted.len(), 1);
+    }
+
+    #[ink::test]
+    fn contract_errors_are_not_submitted() {
+        let mut client = setup_client();
+        let selector = client.metadata().selector("register_product").unwrap();
+        client.runtime_mut().respond(selector, Err::<u32, ContractError>(ContractError::NotAuthorizedManufacturer));
+        
+        assert_eq!(client.send(register_call), Err(ClientError::Contract(ContractError::NotAuthorizedManufacturer)));
+        assert!(client.runtime().submitted.is_empty());
+    }
+
+    #[ink::test]
+    fn query_decodes_outputs_and_dispatch_errors() {
+        let accounts = get_test_accounts();
+        let mut client = setup_client();
+        let owner = client.metadata().selector("get_owner").unwrap();
+        let verify = client.metadata().selector("verify_product").unwrap();
+        client.runtime_mut().respond(owner, accounts.alice);
+        client.runtime_mut().respond_raw(verify, ink::MessageResult::<Option<Product>>::Err(LangError::CouldNotReadInput).encode());
+        
+        assert_eq!(client.query(|contract| contract.get_owner()), Ok(accounts.alice));
+        assert_eq!(client.query(|contract| contract.verify_product(1)), Err(ClientError::Lang(LangError::CouldNotReadInput)));
+        assert!(matches!(client.query(|contract| contract.get_next_product_id()), Err(ClientError::Runtime(_))));
+    }
+}
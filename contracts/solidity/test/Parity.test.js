@@ -0,0 +1,60 @@
+const { expect } = require("chai");
+const { ethers } = require("hardhat");
+
+// Scenarios shared with the ink! contract's `shared_parity_scenarios` test
+const spec = require("../../parity/scenarios.json");
+
+// Expected values are written once for both chains: account names stand in for
+// addresses, and objects only list the fields both implementations return
+function matches(actual, expected, addresses) {
+  if (Array.isArray(expected)) {
+    return actual.length === expected.length && expected.every((value, i) => matches(actual[i], value, addresses));
+  }
+  if (expected !== null && typeof expected === "object") {
+    return Object.keys(expected).every((key) => matches(actual[key], expected[key], addresses));
+  }
+  if (typeof actual === "bigint") {
+    return actual === BigInt(expected);
+  }
+  if (typeof actual === "string" && ethers.isAddress(actual)) {
+    return actual === addresses[expected];
+  }
+  return actual === expected;
+}
+
+describe("MedicalSupplyChain parity scenarios", function () {
+  for (const scenario of spec.scenarios) {
+    it(scenario.name, async function () {
+      const signers = await ethers.getSigners();
+      const accounts = Object.fromEntries(spec.accounts.map((name, i) => [name, signers[i]]));
+      const addresses = Object.fromEntries(spec.accounts.map((name, i) => [name, signers[i].address]));
+
+      const MedicalSupplyChain = await ethers.getContractFactory("MedicalSupplyChain");
+      const medicalSupplyChain = await MedicalSupplyChain.deploy();
+      await medicalSupplyChain.waitForDeployment();
+
+      for (const step of scenario.steps) {
+        const contract = medicalSupplyChain.connect(accounts[step.from || "owner"]);
+        const args = step.args.map((arg) => (typeof arg === "string" && arg in addresses ? addresses[arg] : arg));
+        const fragment = contract.interface.getFunction(step.call);
+        const isView = fragment.stateMutability === "view" || fragment.stateMutability === "pure";
+        const description = `${scenario.name}: ${step.call}(${step.args.join(", ")})`;
+
+        if (step.reverts) {
+          await expect(contract[step.call](...args), description)
+            .to.be.revertedWithCustomError(medicalSupplyChain, step.reverts);
+          continue;
+        }
+
+        // Transactions do not hand back their return value, so read it with a static call first
+        const result = await contract[step.call].staticCall(...args);
+        if (!isView) {
+          await (await contract[step.call](...args)).wait();
+        }
+        if ("returns" in step) {
+          expect(matches(result, step.returns, addresses), description).to.be.true;
+        }
+      }
+    });
+  }
+});